@@ -1,5 +1,7 @@
 use std::{
+    collections::VecDeque,
     error::Error,
+    fmt::Write as _,
     io::{Read, Write},
     process::{Command, Stdio},
     sync::Arc,
@@ -7,39 +9,186 @@ use std::{
 
 use dot::Id;
 use ide_db::{
-    base_db::{CrateGraph, CrateId, Dependency, SourceDatabase, SourceDatabaseExt},
+    base_db::{CrateGraph, CrateId, CrateOrigin, Dependency, SourceDatabase, SourceDatabaseExt},
     RootDatabase,
 };
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Serialize;
 
 // Feature: View Crate Graph
 //
-// Renders the currently loaded crate graph as an SVG graphic. Requires the `dot` tool, which
-// is part of graphviz, to be installed.
+// Renders the currently loaded crate graph as an SVG graphic, or as raw DOT / Mermaid source,
+// as selected by the requested `CrateGraphFormat`; the DOT and Mermaid formats need no graphviz
+// installation. Rendering to SVG requires the `dot` tool, which is part of graphviz, to be
+// installed; there is no automatic fallback when it is missing.
 //
-// Only workspace crates are included, no crates.io dependencies or sysroot crates.
+// By default, only workspace crates are included, no crates.io dependencies or sysroot crates.
+// This can be widened (or narrowed to a single crate's dependencies) via `CrateGraphConfig`.
 //
 // |===
 // | Editor  | Action Name
 //
 // | VS Code | **Rust Analyzer: View Crate Graph**
 // |===
-pub(crate) fn view_crate_graph(db: &RootDatabase) -> Result<String, String> {
+
+/// The output format `view_crate_graph` should produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrateGraphFormat {
+    /// Raw `dot` source, for consumers that don't want to shell out to graphviz themselves.
+    Dot,
+    /// Rendered SVG, via the `dot` binary from graphviz.
+    Svg,
+    /// A `graph LR` Mermaid diagram, renderable by editors without any external tooling.
+    Mermaid,
+}
+
+/// Controls which crates of the loaded `CrateGraph` are included in the rendered graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateGraphConfig {
+    /// Only include crates backed by a local, non-library source root.
+    pub workspace_only: bool,
+    /// Include crates originating from crates.io. Ignored if `workspace_only` is set.
+    pub include_crates_io: bool,
+    /// Include sysroot crates (`core`, `std`, `alloc`, ...). Ignored if `workspace_only` is set.
+    pub include_sysroot: bool,
+    /// Restrict the graph to the transitive dependencies of this crate.
+    pub focus_crate: Option<CrateId>,
+    /// Limit how many dependency edges are followed from `focus_crate`. Has no effect unless
+    /// `focus_crate` is set.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for CrateGraphConfig {
+    fn default() -> Self {
+        CrateGraphConfig {
+            workspace_only: true,
+            include_crates_io: false,
+            include_sysroot: false,
+            focus_crate: None,
+            max_depth: None,
+        }
+    }
+}
+
+pub(crate) fn view_crate_graph(
+    db: &RootDatabase,
+    format: CrateGraphFormat,
+    config: CrateGraphConfig,
+) -> Result<String, String> {
     let crate_graph = db.crate_graph();
-    let crates_to_render = crate_graph
+    let crates_to_render = crates_to_render(db, &crate_graph, &config);
+    let graph = DotCrateGraph { graph: crate_graph, crates_to_render };
+
+    match format {
+        CrateGraphFormat::Dot => {
+            let mut dot = Vec::new();
+            dot::render(&graph, &mut dot).unwrap();
+            String::from_utf8(dot).map_err(|e| e.to_string())
+        }
+        CrateGraphFormat::Svg => {
+            let mut dot = Vec::new();
+            dot::render(&graph, &mut dot).unwrap();
+            render_svg(&dot).map_err(|e| e.to_string())
+        }
+        CrateGraphFormat::Mermaid => Ok(render_mermaid(&graph)),
+    }
+}
+
+/// Serializes the same crate-graph subgraph `view_crate_graph` would render, as JSON, for
+/// consumers (CI dependency auditors, custom visualizers) that want the resolved crate graph
+/// without parsing DOT or depending on graphviz at all.
+pub(crate) fn view_crate_graph_json(
+    db: &RootDatabase,
+    config: CrateGraphConfig,
+) -> Result<String, String> {
+    let crate_graph = db.crate_graph();
+    let crates_to_render = crates_to_render(db, &crate_graph, &config);
+    let graph = DotCrateGraph { graph: crate_graph, crates_to_render };
+
+    let mut deps_by_crate: FxHashMap<CrateId, Vec<DependencyJson>> = FxHashMap::default();
+    for (from, dep) in dot::GraphWalk::edges(&graph).iter() {
+        deps_by_crate
+            .entry(*from)
+            .or_default()
+            .push(DependencyJson { crate_id: dep.crate_id.0, prelude: dep.prelude });
+    }
+
+    let nodes = dot::GraphWalk::nodes(&graph)
         .iter()
-        .filter(|krate| {
-            // Only render workspace crates
-            let root_id = db.file_source_root(crate_graph[*krate].root_file_id);
-            !db.source_root(root_id).is_library
+        .map(|&krate| {
+            let data = &graph.graph[krate];
+            let root_id = db.file_source_root(data.root_file_id);
+            let root_file_path = db
+                .source_root(root_id)
+                .path_for_file(&data.root_file_id)
+                .map(ToString::to_string);
+            CrateNodeJson {
+                id: krate.0,
+                display_name: data.display_name.as_ref().map(ToString::to_string),
+                root_file_path,
+                edition: format!("{:?}", data.edition),
+                cfg: data.cfg_options.iter().map(ToString::to_string).collect(),
+                deps: deps_by_crate.remove(&krate).unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&CrateGraphJson { nodes }).map_err(|e| e.to_string())
+}
+
+/// Computes the set of crates that should be included in the rendered graph for `config`.
+fn crates_to_render(
+    db: &RootDatabase,
+    crate_graph: &CrateGraph,
+    config: &CrateGraphConfig,
+) -> FxHashSet<CrateId> {
+    let mut crates_to_render: FxHashSet<CrateId> = crate_graph
+        .iter()
+        .filter(|&krate| {
+            if config.workspace_only {
+                // Only render workspace crates
+                let root_id = db.file_source_root(crate_graph[krate].root_file_id);
+                return !db.source_root(root_id).is_library;
+            }
+            match crate_graph[krate].origin {
+                CrateOrigin::Lang(_) => config.include_sysroot,
+                CrateOrigin::Library { .. } => config.include_crates_io,
+                CrateOrigin::Local { .. } => true,
+            }
         })
         .collect();
-    let graph = DotCrateGraph { graph: crate_graph, crates_to_render };
 
-    let mut dot = Vec::new();
-    dot::render(&graph, &mut dot).unwrap();
+    if let Some(focus_crate) = config.focus_crate {
+        let reachable = transitive_deps(crate_graph, focus_crate, config.max_depth);
+        crates_to_render.retain(|krate| reachable.contains(krate));
+        // The focus crate itself is always rendered, even if it would otherwise be excluded
+        // by `workspace_only`/`include_crates_io`/`include_sysroot` (e.g. focusing on a
+        // crates.io dependency without opting into `include_crates_io`).
+        crates_to_render.insert(focus_crate);
+    }
+
+    crates_to_render
+}
+
+#[derive(Serialize)]
+struct CrateGraphJson {
+    nodes: Vec<CrateNodeJson>,
+}
+
+#[derive(Serialize)]
+struct CrateNodeJson {
+    id: u32,
+    display_name: Option<String>,
+    root_file_path: Option<String>,
+    edition: String,
+    cfg: Vec<String>,
+    deps: Vec<DependencyJson>,
+}
 
-    render_svg(&dot).map_err(|e| e.to_string())
+#[derive(Serialize)]
+struct DependencyJson {
+    crate_id: u32,
+    prelude: bool,
 }
 
 fn render_svg(dot: &[u8]) -> Result<String, Box<dyn Error>> {
@@ -57,6 +206,47 @@ fn render_svg(dot: &[u8]) -> Result<String, Box<dyn Error>> {
     Ok(svg)
 }
 
+/// Breadth-first walk of `start`'s dependencies, up to `max_depth` edges away (unbounded if
+/// `None`). Always includes `start` itself.
+fn transitive_deps(
+    graph: &CrateGraph,
+    start: CrateId,
+    max_depth: Option<usize>,
+) -> FxHashSet<CrateId> {
+    let mut seen = FxHashSet::default();
+    seen.insert(start);
+
+    let mut queue = VecDeque::new();
+    queue.push_back((start, 0));
+    while let Some((krate, depth)) = queue.pop_front() {
+        if max_depth.map_or(false, |max_depth| depth >= max_depth) {
+            continue;
+        }
+        for dep in &graph[krate].dependencies {
+            if seen.insert(dep.crate_id) {
+                queue.push_back((dep.crate_id, depth + 1));
+            }
+        }
+    }
+    seen
+}
+
+/// Renders `graph` as a Mermaid `graph LR` diagram, without going through the `dot` binary.
+fn render_mermaid(graph: &DotCrateGraph) -> String {
+    let mut mermaid = String::from("graph LR\n");
+    for krate in dot::GraphWalk::nodes(graph).iter().copied() {
+        let name = graph.graph[krate]
+            .display_name
+            .as_ref()
+            .map_or("_missing_name_".to_string(), |name| name.to_string());
+        let _ = writeln!(mermaid, "    crate{}[\"{}\"]", krate.0, name);
+    }
+    for (from, dep) in dot::GraphWalk::edges(graph).iter() {
+        let _ = writeln!(mermaid, "    crate{} --> crate{}", from.0, dep.crate_id.0);
+    }
+    mermaid
+}
+
 struct DotCrateGraph {
     graph: Arc<CrateGraph>,
     crates_to_render: FxHashSet<CrateId>,
@@ -100,4 +290,283 @@ impl<'a> dot::Labeller<'a, CrateId, Edge<'a>> for DotCrateGraph {
         let name = self.graph[*n].display_name.as_ref().map_or("_missing_name_", |name| &*name);
         Id::new(format!("{}_{}", name, n.0)).unwrap()
     }
+
+    fn node_label(&'a self, n: &CrateId) -> dot::LabelText<'a> {
+        let krate = &self.graph[*n];
+        let name = krate
+            .display_name
+            .as_ref()
+            .map_or("_missing_name_".to_string(), |name| name.to_string());
+        let version =
+            krate.version.as_deref().map_or(String::new(), |version| format!(" {}", version));
+        dot::LabelText::LabelStr(format!("{}{}\\n{:?}", name, version, krate.edition).into())
+    }
+
+    fn node_color(&'a self, n: &CrateId) -> Option<dot::LabelText<'a>> {
+        if self.graph[*n].is_proc_macro {
+            Some(dot::LabelText::LabelStr("orange".into()))
+        } else {
+            None
+        }
+    }
+
+    fn edge_label(&'a self, edge: &Edge<'a>) -> dot::LabelText<'a> {
+        if edge.1.prelude {
+            dot::LabelText::LabelStr("prelude".into())
+        } else {
+            dot::LabelText::LabelStr("".into())
+        }
+    }
+
+    fn edge_style(&'a self, edge: &Edge<'a>) -> dot::Style {
+        if edge.1.prelude { dot::Style::Dotted } else { dot::Style::Solid }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ide_db::base_db::{
+        salsa::Durability, CrateDisplayName, CrateName, Edition, FileId, FileSet, LangCrateOrigin,
+        SourceRoot, SourceRootId,
+    };
+
+    use super::*;
+
+    fn add_crate(graph: &mut CrateGraph, name: &str) -> CrateId {
+        add_crate_with_origin(graph, name, CrateOrigin::Local { repo: None, name: None })
+    }
+
+    fn add_crate_with_origin(graph: &mut CrateGraph, name: &str, origin: CrateOrigin) -> CrateId {
+        add_crate_full(graph, name, origin, None, false)
+    }
+
+    fn add_crate_full(
+        graph: &mut CrateGraph,
+        name: &str,
+        origin: CrateOrigin,
+        version: Option<String>,
+        is_proc_macro: bool,
+    ) -> CrateId {
+        graph.add_crate_root(
+            FileId(graph.iter().count() as u32),
+            Edition::Edition2021,
+            Some(CrateDisplayName::from_canonical_name(name.to_string())),
+            version,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            is_proc_macro,
+            origin,
+        )
+    }
+
+    fn add_dep(graph: &mut CrateGraph, from: CrateId, to: CrateId) {
+        let name = CrateName::new("dep").unwrap();
+        graph.add_dep(from, Dependency::with_prelude(name, to, false)).unwrap();
+    }
+
+    /// Builds a `RootDatabase` whose crate graph is `crate_graph`, with every crate's root file
+    /// assigned to a single (empty) local source root. Because that root is local, every crate
+    /// counts as a workspace crate under `workspace_only: true`, so these tests use
+    /// `workspace_only: false` to exercise the origin-based filtering.
+    fn db_with_crate_graph(crate_graph: CrateGraph) -> RootDatabase {
+        let mut db = RootDatabase::default();
+        let root_files: Vec<FileId> =
+            crate_graph.iter().map(|krate| crate_graph[krate].root_file_id).collect();
+
+        db.set_crate_graph_with_durability(Arc::new(crate_graph), Durability::HIGH);
+
+        let root_id = SourceRootId(0);
+        db.set_source_root_with_durability(
+            root_id,
+            Arc::new(SourceRoot::new_local(FileSet::default())),
+            Durability::HIGH,
+        );
+        for file_id in root_files {
+            db.set_file_source_root_with_durability(file_id, root_id, Durability::HIGH);
+        }
+        db
+    }
+
+    #[test]
+    fn transitive_deps_with_no_depth_limit_walks_the_whole_chain() {
+        let mut graph = CrateGraph::default();
+        let a = add_crate(&mut graph, "a");
+        let b = add_crate(&mut graph, "b");
+        let c = add_crate(&mut graph, "c");
+        add_dep(&mut graph, a, b);
+        add_dep(&mut graph, b, c);
+
+        let reachable = transitive_deps(&graph, a, None);
+        assert_eq!(reachable, FxHashSet::from_iter([a, b, c]));
+    }
+
+    #[test]
+    fn transitive_deps_with_max_depth_zero_is_just_the_start_crate() {
+        let mut graph = CrateGraph::default();
+        let a = add_crate(&mut graph, "a");
+        let b = add_crate(&mut graph, "b");
+        add_dep(&mut graph, a, b);
+
+        let reachable = transitive_deps(&graph, a, Some(0));
+        assert_eq!(reachable, FxHashSet::from_iter([a]));
+    }
+
+    #[test]
+    fn transitive_deps_does_not_loop_forever_on_a_diamond() {
+        // `CrateGraph::add_dep` rejects genuine cycles, so a diamond (two paths converging
+        // on the same crate) is the closest shape that still forces `transitive_deps` to
+        // revisit an already-seen crate and prove the `seen` guard works.
+        let mut graph = CrateGraph::default();
+        let a = add_crate(&mut graph, "a");
+        let b = add_crate(&mut graph, "b");
+        let c = add_crate(&mut graph, "c");
+        let d = add_crate(&mut graph, "d");
+        add_dep(&mut graph, a, b);
+        add_dep(&mut graph, a, c);
+        add_dep(&mut graph, b, d);
+        add_dep(&mut graph, c, d);
+
+        let reachable = transitive_deps(&graph, a, None);
+        assert_eq!(reachable, FxHashSet::from_iter([a, b, c, d]));
+    }
+
+    #[test]
+    fn crates_to_render_respects_include_crates_io_and_include_sysroot() {
+        let mut graph = CrateGraph::default();
+        let local = add_crate(&mut graph, "local");
+        let registry = add_crate_with_origin(
+            &mut graph,
+            "registry",
+            CrateOrigin::Library { repo: None, name: None },
+        );
+        let core =
+            add_crate_with_origin(&mut graph, "core", CrateOrigin::Lang(LangCrateOrigin::Core));
+        let db = db_with_crate_graph(graph);
+
+        let base = CrateGraphConfig { workspace_only: false, ..CrateGraphConfig::default() };
+
+        let rendered = crates_to_render(&db, &db.crate_graph(), &base);
+        assert_eq!(rendered, FxHashSet::from_iter([local]));
+
+        let with_crates_io = CrateGraphConfig { include_crates_io: true, ..base.clone() };
+        let rendered = crates_to_render(&db, &db.crate_graph(), &with_crates_io);
+        assert_eq!(rendered, FxHashSet::from_iter([local, registry]));
+
+        let with_sysroot = CrateGraphConfig { include_sysroot: true, ..base };
+        let rendered = crates_to_render(&db, &db.crate_graph(), &with_sysroot);
+        assert_eq!(rendered, FxHashSet::from_iter([local, core]));
+    }
+
+    #[test]
+    fn view_crate_graph_dot_format_emits_raw_dot_source() {
+        let mut graph = CrateGraph::default();
+        let a = add_crate(&mut graph, "a");
+        let b = add_crate(&mut graph, "b");
+        add_dep(&mut graph, a, b);
+        let db = db_with_crate_graph(graph);
+        let config = CrateGraphConfig { workspace_only: false, ..CrateGraphConfig::default() };
+
+        let dot = view_crate_graph(&db, CrateGraphFormat::Dot, config).unwrap();
+        assert!(dot.starts_with("digraph"));
+    }
+
+    #[test]
+    fn view_crate_graph_mermaid_format_emits_graph_lr() {
+        let mut graph = CrateGraph::default();
+        let a = add_crate(&mut graph, "a");
+        let b = add_crate(&mut graph, "b");
+        add_dep(&mut graph, a, b);
+        let db = db_with_crate_graph(graph);
+        let config = CrateGraphConfig { workspace_only: false, ..CrateGraphConfig::default() };
+
+        let mermaid = view_crate_graph(&db, CrateGraphFormat::Mermaid, config).unwrap();
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains(&format!("crate{} --> crate{}", a.0, b.0)));
+    }
+
+    #[test]
+    fn node_label_includes_version_and_edition() {
+        let mut graph = CrateGraph::default();
+        let a = add_crate_full(
+            &mut graph,
+            "a",
+            CrateOrigin::Local { repo: None, name: None },
+            Some("1.2.3".to_string()),
+            false,
+        );
+        let crates_to_render = FxHashSet::from_iter([a]);
+        let dot_graph = DotCrateGraph { graph: Arc::new(graph), crates_to_render };
+
+        match dot_graph.node_label(&a) {
+            dot::LabelText::LabelStr(label) => {
+                assert!(label.contains("a 1.2.3"));
+                assert!(label.contains("Edition2021"));
+            }
+            _ => panic!("expected a LabelStr"),
+        }
+    }
+
+    #[test]
+    fn node_color_marks_proc_macro_crates_but_not_others() {
+        let mut graph = CrateGraph::default();
+        let regular = add_crate(&mut graph, "regular");
+        let proc_macro = add_crate_full(
+            &mut graph,
+            "derive",
+            CrateOrigin::Local { repo: None, name: None },
+            None,
+            true,
+        );
+        let crates_to_render = FxHashSet::from_iter([regular, proc_macro]);
+        let dot_graph = DotCrateGraph { graph: Arc::new(graph), crates_to_render };
+
+        assert!(dot_graph.node_color(&regular).is_none());
+        assert!(dot_graph.node_color(&proc_macro).is_some());
+    }
+
+    #[test]
+    fn edge_label_and_style_leave_non_prelude_deps_unmarked() {
+        let mut graph = CrateGraph::default();
+        let a = add_crate(&mut graph, "a");
+        let b = add_crate(&mut graph, "b");
+        add_dep(&mut graph, a, b);
+        let crates_to_render = FxHashSet::from_iter([a, b]);
+        let dot_graph = DotCrateGraph { graph: Arc::new(graph), crates_to_render };
+
+        let edge = dot::GraphWalk::edges(&dot_graph).iter().next().unwrap();
+        assert!(matches!(dot_graph.edge_style(&edge), dot::Style::Solid));
+        match dot_graph.edge_label(&edge) {
+            dot::LabelText::LabelStr(label) => assert_eq!(label, ""),
+            _ => panic!("expected a LabelStr"),
+        }
+    }
+
+    #[test]
+    fn view_crate_graph_json_round_trips_a_two_crate_graph() {
+        let mut graph = CrateGraph::default();
+        let a = add_crate(&mut graph, "a");
+        let b = add_crate(&mut graph, "b");
+        add_dep(&mut graph, a, b);
+        let db = db_with_crate_graph(graph);
+        let config = CrateGraphConfig { workspace_only: false, ..CrateGraphConfig::default() };
+
+        let json = view_crate_graph_json(&db, config).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let nodes = value["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 2);
+
+        let node_a = nodes.iter().find(|node| node["id"] == a.0).unwrap();
+        assert_eq!(node_a["display_name"], "a");
+        assert_eq!(node_a["edition"], "Edition2021");
+        let deps = node_a["deps"].as_array().unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0]["crate_id"], b.0);
+        assert_eq!(deps[0]["prelude"], false);
+
+        let node_b = nodes.iter().find(|node| node["id"] == b.0).unwrap();
+        assert_eq!(node_b["deps"].as_array().unwrap().len(), 0);
+    }
 }